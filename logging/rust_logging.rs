@@ -3,19 +3,31 @@ Enhanced logging configuration for Off the Grid Rust CLI
 Provides structured logging with correlation IDs, performance metrics, and security events
 */
 
-use std::io::{self, Write};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, OnceLock};
 use serde_json::{json, Value};
+use tracing::span::{Attributes, Id, Record};
 use tracing::{Event, Subscriber};
 use tracing_subscriber::{
+    filter::{FilterExt, LevelFilter},
     fmt::{self, format::Writer, FormatEvent, FormatFields},
-    layer::SubscriberExt,
+    layer::{Context, Filter, SubscriberExt},
+    reload,
     util::SubscriberInitExt,
     EnvFilter, Layer,
 };
 use tracing_appender::{non_blocking, rolling};
+use tracing_appender::non_blocking::WorkerGuard;
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
+use chrono::Utc;
+use opentelemetry::{global, KeyValue};
+use opentelemetry::metrics::Histogram;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{metrics::SdkMeterProvider, runtime, Resource};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 
 /// Correlation ID for request tracing
 #[derive(Clone, Debug)]
@@ -31,6 +43,12 @@ impl CorrelationId {
     }
 }
 
+impl Default for CorrelationId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Security event types
 #[derive(Clone, Debug, serde::Serialize)]
 pub enum SecurityEventType {
@@ -65,6 +83,12 @@ pub enum BusinessEventType {
     WalletBalanceChanged,
 }
 
+/// Histogram instrument used to record `duration_ms` from [`log_performance_metric`].
+///
+/// Populated once by [`init_logging`] when an OTLP endpoint is configured; metrics
+/// recorded before that point are simply skipped.
+static PERFORMANCE_DURATION_HISTOGRAM: OnceLock<Histogram<f64>> = OnceLock::new();
+
 /// Custom JSON formatter for structured logging
 pub struct StructuredJsonFormatter;
 
@@ -79,55 +103,68 @@ where
         mut writer: Writer<'_>,
         event: &Event<'_>,
     ) -> std::fmt::Result {
-        let metadata = event.metadata();
-        
-        let mut json_log = json!({
-            "timestamp": Utc::now().to_rfc3339(),
-            "level": metadata.level().to_string(),
-            "target": metadata.target(),
-            "service": "off-the-grid-cli",
-            "environment": std::env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string())
-        });
-
         // Add span context if available
-        if let Some(span) = ctx.lookup_current() {
-            let extensions = span.extensions();
-            
-            // Add correlation ID if present
-            if let Some(correlation_id) = extensions.get::<CorrelationId>() {
-                json_log["correlation_id"] = json!(correlation_id.0);
+        let (span_name, correlation_id) = match ctx.lookup_current() {
+            Some(span) => {
+                let extensions = span.extensions();
+                let correlation_id = extensions.get::<CorrelationId>().map(|c| c.0.clone());
+                (Some(span.name()), correlation_id)
             }
-            
-            // Add span name
-            json_log["span"] = json!(span.name());
-        }
-
-        // Extract message and fields from event
-        let mut visitor = JsonVisitor::new();
-        event.record(&mut visitor);
-        
-        if let Some(message) = visitor.message {
-            json_log["message"] = json!(message);
-        }
-        
-        // Add custom fields
-        for (key, value) in visitor.fields {
-            json_log[key] = value;
-        }
+            None => (None, None),
+        };
 
-        // Add file and line if available in debug mode
-        if let Some(file) = metadata.file() {
-            json_log["file"] = json!(file);
-        }
-        if let Some(line) = metadata.line() {
-            json_log["line"] = json!(line);
-        }
+        let json_log = event_to_json(event, span_name, correlation_id.as_deref());
 
         writeln!(writer, "{}", json_log)?;
         Ok(())
     }
 }
 
+/// Render an event into the same JSON shape used by [`StructuredJsonFormatter`],
+/// without requiring a `fmt::FmtContext`. Shared by the formatter itself and by
+/// [`PerEntityFileLayer`], which serializes events outside of the fmt layer.
+fn event_to_json(event: &Event<'_>, span_name: Option<&str>, correlation_id: Option<&str>) -> Value {
+    let metadata = event.metadata();
+
+    let mut json_log = json!({
+        "timestamp": Utc::now().to_rfc3339(),
+        "level": metadata.level().to_string(),
+        "target": metadata.target(),
+        "service": "off-the-grid-cli",
+        "environment": std::env::var("ENVIRONMENT").unwrap_or_else(|_| "development".to_string())
+    });
+
+    if let Some(correlation_id) = correlation_id {
+        json_log["correlation_id"] = json!(correlation_id);
+    }
+    if let Some(span_name) = span_name {
+        json_log["span"] = json!(span_name);
+    }
+
+    // Extract message and fields from event
+    let mut visitor = JsonVisitor::new();
+    event.record(&mut visitor);
+
+    if let Some(message) = visitor.message {
+        json_log["message"] = json!(message);
+    }
+
+    // Add custom fields
+    for (key, value) in visitor.fields {
+        json_log[key] = value;
+    }
+
+    // Add file and line if available in debug mode
+    if let Some(file) = metadata.file() {
+        json_log["file"] = json!(file);
+    }
+    if let Some(line) = metadata.line() {
+        json_log["line"] = json!(line);
+    }
+
+    json_log
+}
+
 /// Visitor for extracting fields from events
 struct JsonVisitor {
     message: Option<String>,
@@ -175,55 +212,733 @@ impl tracing::field::Visit for JsonVisitor {
     fn record_bool(&mut self, field: &tracing::field::Field, value: bool) {
         self.fields.insert(field.name().to_string(), json!(value));
     }
+
+    // Fields recorded as a `valuable::Value` (see `valuable_json::JsonMap`) are
+    // walked into a real JSON tree instead of falling back to `record_debug`,
+    // which would stringify maps/lists into an opaque blob.
+    #[cfg(all(tracing_unstable, feature = "valuable"))]
+    fn record_value(&mut self, field: &tracing::field::Field, value: valuable::Value<'_>) {
+        let json_value = valuable_json::to_json(value);
+        if field.name() == "message" {
+            self.message = Some(json_value.to_string());
+        } else {
+            self.fields.insert(field.name().to_string(), json_value);
+        }
+    }
+}
+
+/// Bridges `valuable::Value` and `serde_json::Value` in both directions so
+/// structured data (maps, lists, nested structs) survives the trip through
+/// `tracing` instead of being flattened into a `Debug` string.
+#[cfg(all(tracing_unstable, feature = "valuable"))]
+mod valuable_json {
+    use super::*;
+    use valuable::{Listable, Mappable, Valuable, Value as VValue, Visit};
+
+    /// Walks a `valuable::Value` (as received by `JsonVisitor::record_value`)
+    /// into the equivalent `serde_json::Value`, preserving maps and lists.
+    pub fn to_json(value: VValue<'_>) -> Value {
+        match value {
+            VValue::Bool(b) => json!(b),
+            VValue::Char(c) => json!(c.to_string()),
+            VValue::F32(f) => json!(f),
+            VValue::F64(f) => json!(f),
+            VValue::I8(i) => json!(i),
+            VValue::I16(i) => json!(i),
+            VValue::I32(i) => json!(i),
+            VValue::I64(i) => json!(i),
+            VValue::I128(i) => json!(i.to_string()),
+            VValue::Isize(i) => json!(i),
+            VValue::U8(u) => json!(u),
+            VValue::U16(u) => json!(u),
+            VValue::U32(u) => json!(u),
+            VValue::U64(u) => json!(u),
+            VValue::U128(u) => json!(u.to_string()),
+            VValue::Usize(u) => json!(u),
+            VValue::String(s) => json!(s),
+            VValue::Path(p) => json!(p.display().to_string()),
+            VValue::Error(e) => json!(e.to_string()),
+            VValue::Unit => Value::Null,
+            VValue::Listable(listable) => {
+                let mut items = Vec::new();
+                listable.visit(&mut CollectList(&mut items));
+                Value::Array(items)
+            }
+            VValue::Mappable(mappable) => {
+                let mut entries = serde_json::Map::new();
+                mappable.visit(&mut CollectMap(&mut entries));
+                Value::Object(entries)
+            }
+            VValue::Structable(structable) => {
+                let mut entries = serde_json::Map::new();
+                structable.visit(&mut CollectStruct(&mut entries));
+                Value::Object(entries)
+            }
+            VValue::Enumerable(enumerable) => {
+                let mut entries = serde_json::Map::new();
+                enumerable.visit(&mut CollectStruct(&mut entries));
+                Value::Object(entries)
+            }
+            VValue::Tuplable(tuplable) => {
+                let mut items = Vec::new();
+                tuplable.visit(&mut CollectList(&mut items));
+                Value::Array(items)
+            }
+            _ => Value::Null,
+        }
+    }
+
+    struct CollectList<'a>(&'a mut Vec<Value>);
+    impl Visit for CollectList<'_> {
+        fn visit_value(&mut self, value: VValue<'_>) {
+            self.0.push(to_json(value));
+        }
+    }
+
+    struct CollectMap<'a>(&'a mut serde_json::Map<String, Value>);
+    impl Visit for CollectMap<'_> {
+        fn visit_value(&mut self, _value: VValue<'_>) {
+            // `mappable.visit` only ever drives us via `visit_entry`.
+        }
+
+        fn visit_entry(&mut self, key: VValue<'_>, value: VValue<'_>) {
+            let key = match to_json(key) {
+                Value::String(s) => s,
+                other => other.to_string(),
+            };
+            self.0.insert(key, to_json(value));
+        }
+    }
+
+    struct CollectStruct<'a>(&'a mut serde_json::Map<String, Value>);
+    impl Visit for CollectStruct<'_> {
+        fn visit_named_fields(&mut self, named_values: &valuable::NamedValues<'_>) {
+            for (field, value) in named_values.iter() {
+                self.0.insert(field.name().to_string(), to_json(*value));
+            }
+        }
+
+        fn visit_unnamed_fields(&mut self, values: &[VValue<'_>]) {
+            for (index, value) in values.iter().enumerate() {
+                self.0.insert(index.to_string(), to_json(*value));
+            }
+        }
+
+        fn visit_value(&mut self, value: VValue<'_>) {
+            self.0.insert("value".to_string(), to_json(value));
+        }
+    }
+
+    /// Recurses a borrowed `serde_json::Value` into `valuable::Visit` calls,
+    /// constructing list/map wrappers inline so their borrows never need to
+    /// outlive this call -- the mirror image of [`to_json`] above.
+    fn emit(value: &Value, push: &mut dyn FnMut(VValue<'_>)) {
+        match value {
+            Value::Null => push(VValue::Unit),
+            Value::Bool(b) => push(VValue::Bool(*b)),
+            Value::Number(n) => push(number_to_valuable(n)),
+            Value::String(s) => push(VValue::String(s)),
+            Value::Array(items) => push(VValue::Listable(&JsonArray(items))),
+            Value::Object(entries) => push(VValue::Mappable(&JsonObject(entries))),
+        }
+    }
+
+    fn number_to_valuable(n: &serde_json::Number) -> VValue<'_> {
+        if let Some(i) = n.as_i64() {
+            VValue::I64(i)
+        } else if let Some(u) = n.as_u64() {
+            VValue::U64(u)
+        } else {
+            VValue::F64(n.as_f64().unwrap_or_default())
+        }
+    }
+
+    struct JsonArray<'a>(&'a [Value]);
+
+    impl Valuable for JsonArray<'_> {
+        fn as_value(&self) -> VValue<'_> {
+            VValue::Listable(self)
+        }
+
+        fn visit(&self, visit: &mut dyn Visit) {
+            for item in self.0 {
+                emit(item, &mut |v| visit.visit_value(v));
+            }
+        }
+    }
+
+    impl Listable for JsonArray<'_> {
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (self.0.len(), Some(self.0.len()))
+        }
+    }
+
+    struct JsonObject<'a>(&'a serde_json::Map<String, Value>);
+
+    impl Valuable for JsonObject<'_> {
+        fn as_value(&self) -> VValue<'_> {
+            VValue::Mappable(self)
+        }
+
+        fn visit(&self, visit: &mut dyn Visit) {
+            for (key, value) in self.0.iter() {
+                emit(value, &mut |v| visit.visit_entry(VValue::String(key), v));
+            }
+        }
+    }
+
+    impl Mappable for JsonObject<'_> {
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (self.0.len(), Some(self.0.len()))
+        }
+    }
+
+    /// Adapts a `HashMap<String, Value>` `details` map so it can be recorded as
+    /// a single structured `valuable::Value` field instead of via `record_debug`.
+    pub struct JsonMap<'a>(pub &'a HashMap<String, Value>);
+
+    impl Valuable for JsonMap<'_> {
+        fn as_value(&self) -> VValue<'_> {
+            VValue::Mappable(self)
+        }
+
+        fn visit(&self, visit: &mut dyn Visit) {
+            for (key, value) in self.0.iter() {
+                emit(value, &mut |v| visit.visit_entry(VValue::String(key), v));
+            }
+        }
+    }
+
+    impl Mappable for JsonMap<'_> {
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (self.0.len(), Some(self.0.len()))
+        }
+    }
+}
+
+/// Guards that keep the logging pipeline alive.
+///
+/// `tracing_appender`'s `WorkerGuard`s flush their background writer threads on
+/// drop, and the OTEL providers flush/shutdown their exporters on drop as well.
+/// Hold this value for the lifetime of the process (e.g. bind it in `main`) --
+/// dropping it early silently truncates in-flight log and trace data.
+#[must_use = "dropping the logging guards immediately flushes and tears down the pipeline"]
+pub struct LoggingGuards {
+    _app_guard: WorkerGuard,
+    _error_guard: WorkerGuard,
+    _security_guard: WorkerGuard,
+    _performance_guard: WorkerGuard,
+    _otel: Option<OtelGuard>,
+}
+
+/// Holds the OTEL meter provider so it can be flushed and shut down when the
+/// process exits. The tracer provider is shut down through the global OTEL
+/// registry instead, since `install_batch` registers it there directly and
+/// hands back only a `Tracer`.
+struct OtelGuard {
+    meter_provider: SdkMeterProvider,
+    // Only `Some` when [`build_otel_layer`] had to spin up its own runtime
+    // because it wasn't called from inside one. Keeping it here, rather than
+    // letting it drop at the end of that function, is what keeps the batch
+    // exporter's background flush/export tasks running for the rest of the
+    // process; dropped last, after `meter_provider.shutdown()` below.
+    _otel_runtime: Option<tokio::runtime::Runtime>,
+}
+
+impl Drop for OtelGuard {
+    fn drop(&mut self) {
+        global::shutdown_tracer_provider();
+        if let Err(err) = self.meter_provider.shutdown() {
+            eprintln!("error shutting down OTEL meter provider: {err}");
+        }
+    }
+}
+
+/// Boxed OTEL layer plus the guard that flushes/shuts down its providers on drop.
+type OtelLayerAndGuard<S> = (Box<dyn Layer<S> + Send + Sync>, OtelGuard);
+
+/// Build the OTLP tracer/meter providers and register the global meter used by
+/// [`log_performance_metric`]. Returns `None` when no endpoint is configured, in
+/// which case logging falls back to the stdout/file layers only.
+fn build_otel_layer<S>(
+    otel_endpoint: Option<String>,
+) -> Result<Option<OtelLayerAndGuard<S>>, Box<dyn std::error::Error>>
+where
+    S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a> + Send + Sync,
+{
+    let endpoint = otel_endpoint
+        .or_else(|| std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok());
+
+    let Some(endpoint) = endpoint else {
+        return Ok(None);
+    };
+
+    // `install_batch(runtime::Tokio)`/`.metrics(runtime::Tokio)` call `tokio::spawn`
+    // under the hood, which panics outside of a Tokio runtime -- and the batch
+    // exporter keeps spawning periodic flush tasks onto that same runtime for as
+    // long as it runs. `init_logging` is a plain sync function that may be called
+    // before a runtime exists (e.g. at the top of `main`, ahead of `#[tokio::main]`),
+    // so spin up a dedicated one here when there isn't an ambient one already, and
+    // keep it alive in `OtelGuard` for the rest of the process.
+    let otel_runtime = match tokio::runtime::Handle::try_current() {
+        Ok(_) => None,
+        Err(_) => Some(
+            tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .thread_name("otel-exporter")
+                .build()?,
+        ),
+    };
+    let _enter = otel_runtime.as_ref().map(|rt| rt.enter());
+
+    let resource = Resource::new(vec![KeyValue::new("service.name", "off-the-grid-cli")]);
+
+    // `install_batch` builds the provider, hands it to the global OTEL registry,
+    // and returns just the `Tracer` -- shutdown happens via `global::shutdown_tracer_provider`.
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(&endpoint),
+        )
+        .with_trace_config(
+            opentelemetry_sdk::trace::config().with_resource(resource.clone()),
+        )
+        .install_batch(runtime::Tokio)?;
+
+    let meter_provider = opentelemetry_otlp::new_pipeline()
+        .metrics(runtime::Tokio)
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(&endpoint),
+        )
+        .with_resource(resource)
+        .build()?;
+    global::set_meter_provider(meter_provider.clone());
+
+    let meter = global::meter("off-the-grid-cli");
+    let histogram = meter
+        .f64_histogram("operation.duration_ms")
+        .with_description("Duration of instrumented operations, in milliseconds")
+        .init();
+    let _ = PERFORMANCE_DURATION_HISTOGRAM.set(histogram);
+
+    let otel_layer = tracing_opentelemetry::layer().with_tracer(tracer).boxed();
+
+    Ok(Some((
+        otel_layer,
+        OtelGuard {
+            meter_provider,
+            _otel_runtime: otel_runtime,
+        },
+    )))
+}
+
+/// Names of the span fields that identify the business entity a span belongs to,
+/// in priority order: if a span carries more than one of these fields, the one
+/// listed first here wins, regardless of the order they were declared in the
+/// `span!`/`info_span!` call. See [`EntityIdVisitor::resolve`].
+const ENTITY_ID_FIELDS: [&str; 2] = ["grid_order_id", "correlation_id"];
+
+/// Per-span file handle for [`PerEntityFileLayer`], stashed in the span's
+/// extensions so events can find their way to the right file as they bubble up.
+struct EntityWriter(Mutex<BufWriter<File>>);
+
+/// Collects every field in [`ENTITY_ID_FIELDS`] present on a span's attributes,
+/// then [`resolve`](Self::resolve)s them by the array's priority order rather
+/// than by whichever field happened to be visited first.
+#[derive(Default)]
+struct EntityIdVisitor {
+    found: HashMap<&'static str, String>,
+}
+
+impl EntityIdVisitor {
+    fn record(&mut self, field_name: &str, value: impl FnOnce() -> String) {
+        if let Some(&key) = ENTITY_ID_FIELDS.iter().find(|&&f| f == field_name) {
+            self.found.entry(key).or_insert_with(value);
+        }
+    }
+
+    /// Returns the value of the highest-priority field present, per
+    /// [`ENTITY_ID_FIELDS`] order -- independent of the order fields were
+    /// declared in the `span!`/`info_span!` call.
+    fn resolve(&self) -> Option<&str> {
+        ENTITY_ID_FIELDS
+            .iter()
+            .find_map(|name| self.found.get(name))
+            .map(|s| s.as_str())
+    }
+}
+
+impl tracing::field::Visit for EntityIdVisitor {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.record(field.name(), || format!("{:?}", value).trim_matches('"').to_string());
+    }
+
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        self.record(field.name(), || value.to_string());
+    }
+}
+
+/// Validates a raw `grid_order_id`/`correlation_id` field value before it's used
+/// as a filename, rejecting anything that could escape `base_dir` (path
+/// separators, `.`/`..`, or empty) rather than trusting a value that ultimately
+/// comes from span fields.
+fn sanitize_entity_id(raw: &str) -> Option<String> {
+    let trimmed = raw.trim();
+    let valid = !trimmed.is_empty()
+        && trimmed != "."
+        && trimmed != ".."
+        && !trimmed.contains(['/', '\\']);
+    valid.then(|| trimmed.to_string())
+}
+
+/// Layer that gives each business entity (grid order, matcher session, ...) its
+/// own audit trail: any span carrying a `grid_order_id` or `correlation_id` field
+/// gets a dedicated append-only log file under `orders/`, and every event emitted
+/// inside that span (or a child span) is additionally written there, on top of
+/// the global app.log.
+pub struct PerEntityFileLayer {
+    base_dir: PathBuf,
+    dir_ready: std::sync::Once,
+}
+
+impl PerEntityFileLayer {
+    pub fn new() -> Self {
+        Self {
+            base_dir: PathBuf::from("/var/log/off-the-grid/orders"),
+            dir_ready: std::sync::Once::new(),
+        }
+    }
+
+    /// Test-only constructor: points the layer at an arbitrary directory
+    /// instead of the hardcoded `/var/log/off-the-grid/orders`, so behavior
+    /// can be exercised against a throwaway temp dir.
+    #[cfg(test)]
+    fn with_base_dir(base_dir: PathBuf) -> Self {
+        Self {
+            base_dir,
+            dir_ready: std::sync::Once::new(),
+        }
+    }
+
+    fn ensure_log_dir(&self) {
+        self.dir_ready.call_once(|| {
+            if let Err(err) = std::fs::create_dir_all(&self.base_dir) {
+                eprintln!("failed to create per-entity log directory: {err}");
+            }
+        });
+    }
+
+    /// Opens (once) and wires up the per-entity writer for `id`, unless it's
+    /// already there -- shared between `on_new_span` (the id is known up front)
+    /// and `on_record` (the id is filled in after the span is created, e.g.
+    /// `info_span!("order", grid_order_id = Empty)` followed by `span.record(..)`).
+    fn install_entity_writer<S>(&self, id: &Id, entity_id: &str, ctx: &Context<'_, S>)
+    where
+        S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        if span.extensions().get::<EntityWriter>().is_some() {
+            return;
+        }
+
+        self.ensure_log_dir();
+
+        let path = self.base_dir.join(format!("{entity_id}.log"));
+        match OpenOptions::new().create(true).append(true).open(&path) {
+            Ok(file) => {
+                span.extensions_mut()
+                    .insert(EntityWriter(Mutex::new(BufWriter::new(file))));
+            }
+            Err(err) => eprintln!("failed to open per-entity log file {path:?}: {err}"),
+        }
+    }
+}
+
+impl Default for PerEntityFileLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for PerEntityFileLayer
+where
+    S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = EntityIdVisitor::default();
+        attrs.record(&mut visitor);
+
+        if let Some(entity_id) = visitor.resolve().and_then(sanitize_entity_id) {
+            self.install_entity_writer(id, &entity_id, &ctx);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let mut visitor = EntityIdVisitor::default();
+        values.record(&mut visitor);
+
+        if let Some(entity_id) = visitor.resolve().and_then(sanitize_entity_id) {
+            self.install_entity_writer(id, &entity_id, &ctx);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(scope) = ctx.event_scope(event) else {
+            return;
+        };
+
+        for span in scope {
+            let extensions = span.extensions();
+            let Some(entity_writer) = extensions.get::<EntityWriter>() else {
+                continue;
+            };
+
+            let correlation_id = extensions.get::<CorrelationId>().map(|c| c.0.clone());
+            let json_log = event_to_json(event, Some(span.name()), correlation_id.as_deref());
+
+            if let Ok(mut writer) = entity_writer.0.lock() {
+                let _ = writeln!(writer, "{json_log}")
+                    .inspect_err(|err| eprintln!("failed to write per-entity log line: {err}"));
+            }
+            break;
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let extensions = span.extensions();
+        // Flushes whatever the `BufWriter` still holds; the file itself is closed
+        // when the registry drops this span's extensions. The two `if let`s are
+        // kept nested on purpose, hence the lint allow.
+        #[allow(clippy::collapsible_if)]
+        if let Some(entity_writer) = extensions.get::<EntityWriter>() {
+            if let Ok(mut writer) = entity_writer.0.lock() {
+                let _ = writer.flush();
+            }
+        }
+    }
+}
+
+/// Rendering format for the stdout/file fmt layers.
+///
+/// Parses from a CLI flag or env var via `FromStr`, e.g. `--log-output=compact`
+/// or `LOG_OUTPUT=json`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogOutput {
+    /// The existing `StructuredJsonFormatter`, one JSON object per line.
+    Json,
+    /// `tracing_subscriber`'s multi-line pretty format, handy for local debugging.
+    Pretty,
+    /// `tracing_subscriber`'s single-line compact format, handy for interactive runs.
+    Compact,
+    /// The default human-readable `fmt` format. This is the historical default.
+    #[default]
+    Log,
+    /// Suppress output on this layer entirely.
+    None,
+}
+
+impl std::str::FromStr for LogOutput {
+    type Err = LogOutputParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(LogOutput::Json),
+            "pretty" => Ok(LogOutput::Pretty),
+            "compact" => Ok(LogOutput::Compact),
+            "log" => Ok(LogOutput::Log),
+            "none" => Ok(LogOutput::None),
+            other => Err(LogOutputParseError(other.to_string())),
+        }
+    }
+}
+
+/// Error returned when parsing an unrecognized [`LogOutput`] value.
+#[derive(Clone, Debug)]
+pub struct LogOutputParseError(String);
+
+impl std::fmt::Display for LogOutputParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown log output format '{}' (expected one of: json, pretty, compact, log, none)",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for LogOutputParseError {}
+
+/// Build an `fmt` layer configured for the given [`LogOutput`].
+///
+/// `LogOutput::None` is rendered as a real layer filtered down to nothing,
+/// rather than `Option::None`, so callers can unconditionally suppress just
+/// this one layer (e.g. stdout) while leaving sibling layers (e.g. the file
+/// layer) on their own format.
+fn configured_fmt_layer<W, F, S>(
+    output: LogOutput,
+    writer: W,
+    filter: F,
+) -> Box<dyn Layer<S> + Send + Sync>
+where
+    W: for<'writer> fmt::MakeWriter<'writer> + Send + Sync + 'static,
+    F: Filter<S> + Send + Sync + 'static,
+    S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    match output {
+        LogOutput::Json => fmt::layer()
+            .event_format(StructuredJsonFormatter)
+            .with_writer(writer)
+            .with_filter(filter)
+            .boxed(),
+        LogOutput::Pretty => fmt::layer()
+            .pretty()
+            .with_writer(writer)
+            .with_filter(filter)
+            .boxed(),
+        LogOutput::Compact => fmt::layer()
+            .compact()
+            .with_writer(writer)
+            .with_filter(filter)
+            .boxed(),
+        LogOutput::Log => fmt::layer().with_writer(writer).with_filter(filter).boxed(),
+        // `.and(LevelFilter::OFF)` rather than swapping in a fresh `EnvFilter::new("off")`:
+        // the latter would build a standalone filter that's never installed in the
+        // subscriber, leaving `filter` (and any `reload::Layer` wrapping it) orphaned --
+        // silently breaking `ReloadHandle::set_filter` for this layer.
+        LogOutput::None => fmt::layer()
+            .with_writer(writer)
+            .with_filter(filter.and(LevelFilter::OFF))
+            .boxed(),
+    }
+}
+
+/// A single layer's reload callback, with its `reload::Handle<EnvFilter, S>`
+/// type erased: each layer in the `registry().with(...).with(...)` chain is
+/// checked against a different, progressively-nested `S`, so the stdout and
+/// file handles are never the same concrete type. Boxing the call keeps
+/// [`ReloadHandle`] simple instead of having to name that type.
+type FilterReloadFn = Arc<dyn Fn(EnvFilter) -> Result<(), reload::Error> + Send + Sync>;
+
+/// Wraps `filter` in a [`reload::Layer`] and returns it alongside a
+/// type-erased callback for swapping it out later.
+fn reloadable_filter<S>(filter: EnvFilter) -> (reload::Layer<EnvFilter, S>, FilterReloadFn)
+where
+    S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    let (layer, handle) = reload::Layer::new(filter);
+    let reload_fn: FilterReloadFn = Arc::new(move |new_filter| handle.reload(new_filter));
+    (layer, reload_fn)
+}
+
+/// Handle for reconfiguring the live stdout/file `EnvFilter`s without a restart.
+///
+/// Returned by [`init_logging`]; `set_filter` re-parses the directives once and
+/// applies the result to both the stdout and file layers.
+#[derive(Clone)]
+pub struct ReloadHandle {
+    reload_stdout: FilterReloadFn,
+    reload_file: FilterReloadFn,
+}
+
+impl ReloadHandle {
+    /// Re-parse `directives` (e.g. `"info,security=debug,performance=trace"`) and
+    /// swap the live filter in place. Emits a `ConfigurationChange` security event
+    /// so the change itself is auditable.
+    pub fn set_filter(&self, directives: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let new_filter = EnvFilter::try_new(directives)?;
+        (self.reload_stdout)(new_filter.clone())?;
+        (self.reload_file)(new_filter)?;
+
+        let mut details = HashMap::new();
+        details.insert("new_filter".to_string(), json!(directives));
+        log_security_event(SecurityEventType::ConfigurationChange, None, details);
+
+        Ok(())
+    }
+}
+
+/// Spawn a background thread that reloads the log filter from `directives_env_var`
+/// every time the process receives `SIGHUP`, e.g. `kill -HUP <pid>`. This gives
+/// operators on-the-fly debug escalation on a long-running matcher bot without
+/// dropping in-flight orders.
+pub fn spawn_sighup_reload_listener(handle: ReloadHandle, directives_env_var: &'static str) {
+    std::thread::spawn(move || {
+        let mut signals =
+            match signal_hook::iterator::Signals::new([signal_hook::consts::SIGHUP]) {
+                Ok(signals) => signals,
+                Err(err) => {
+                    eprintln!("failed to install SIGHUP handler: {err}");
+                    return;
+                }
+            };
+
+        for _ in signals.forever() {
+            let directives =
+                std::env::var(directives_env_var).unwrap_or_else(|_| "info".to_string());
+            if let Err(err) = handle.set_filter(&directives) {
+                eprintln!("failed to reload log filter from SIGHUP: {err}");
+            }
+        }
+    });
 }
 
-/// Initialize comprehensive logging system
-pub fn init_logging(log_level: &str, json_format: bool) -> Result<(), Box<dyn std::error::Error>> {
+/// Initialize comprehensive logging system.
+///
+/// `otel_endpoint` (or the `OTEL_EXPORTER_OTLP_ENDPOINT` env var) optionally points
+/// at an OpenTelemetry collector; when set, spans and performance metrics are
+/// additionally exported via OTLP alongside the existing stdout/file layers.
+/// `log_output` selects the stdout rendering; `LogOutput::None` silences stdout
+/// while the file layer keeps logging in its default human-readable format.
+/// Returns a [`ReloadHandle`] alongside the usual guards so callers can escalate
+/// verbosity at runtime (see [`spawn_sighup_reload_listener`]).
+pub fn init_logging(
+    log_level: &str,
+    log_output: LogOutput,
+    otel_endpoint: Option<String>,
+) -> Result<(LoggingGuards, ReloadHandle), Box<dyn std::error::Error>> {
     // Create log directory
     std::fs::create_dir_all("/var/log/off-the-grid")?;
 
     // Create file appenders
     let file_appender = rolling::daily("/var/log/off-the-grid", "app.log");
-    let (non_blocking, _guard) = non_blocking(file_appender);
+    let (app_non_blocking, app_guard) = non_blocking(file_appender);
 
     let error_appender = rolling::daily("/var/log/off-the-grid", "error.log");
-    let (error_non_blocking, _error_guard) = non_blocking(error_appender);
+    let (error_non_blocking, error_guard) = non_blocking(error_appender);
 
     let security_appender = rolling::daily("/var/log/off-the-grid", "security.log");
-    let (security_non_blocking, _security_guard) = non_blocking(security_appender);
+    let (security_non_blocking, security_guard) = non_blocking(security_appender);
 
     let performance_appender = rolling::daily("/var/log/off-the-grid", "performance.log");
-    let (performance_non_blocking, _performance_guard) = non_blocking(performance_appender);
+    let (performance_non_blocking, performance_guard) = non_blocking(performance_appender);
 
     // Create layers
     let env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new(log_level));
 
-    let stdout_layer = if json_format {
-        fmt::layer()
-            .event_format(StructuredJsonFormatter)
-            .with_writer(io::stdout)
-            .with_filter(env_filter.clone())
-            .boxed()
-    } else {
-        fmt::layer()
-            .with_writer(io::stdout)
-            .with_filter(env_filter.clone())
-            .boxed()
-    };
+    // Wrapping each filter in a reload layer lets `ReloadHandle::set_filter` swap
+    // them out live without a restart.
+    let (stdout_filter, reload_stdout) = reloadable_filter(env_filter.clone());
+    let stdout_layer = configured_fmt_layer(log_output, io::stdout, stdout_filter);
 
-    let file_layer = if json_format {
-        fmt::layer()
-            .event_format(StructuredJsonFormatter)
-            .with_writer(non_blocking)
-            .with_filter(env_filter.clone())
-            .boxed()
+    // `None` only silences stdout; the file layer still needs to log something,
+    // so it falls back to the default human-readable format instead of going dark too.
+    let file_output = if log_output == LogOutput::None {
+        LogOutput::Log
     } else {
-        fmt::layer()
-            .with_writer(non_blocking)
-            .with_filter(env_filter.clone())
-            .boxed()
+        log_output
     };
+    let (file_filter, reload_file) = reloadable_filter(env_filter);
+    let file_layer = configured_fmt_layer(file_output, app_non_blocking, file_filter);
 
     let error_layer = fmt::layer()
         .event_format(StructuredJsonFormatter)
@@ -249,6 +964,11 @@ pub fn init_logging(log_level: &str, json_format: bool) -> Result<(), Box<dyn st
         )
         .boxed();
 
+    let (otel_layer, otel_guard) = match build_otel_layer(otel_endpoint)? {
+        Some((layer, guard)) => (Some(layer), Some(guard)),
+        None => (None, None),
+    };
+
     // Initialize subscriber
     tracing_subscriber::registry()
         .with(stdout_layer)
@@ -256,9 +976,23 @@ pub fn init_logging(log_level: &str, json_format: bool) -> Result<(), Box<dyn st
         .with(error_layer)
         .with(security_layer)
         .with(performance_layer)
+        .with(otel_layer)
+        .with(PerEntityFileLayer::new())
         .init();
 
-    Ok(())
+    let guards = LoggingGuards {
+        _app_guard: app_guard,
+        _error_guard: error_guard,
+        _security_guard: security_guard,
+        _performance_guard: performance_guard,
+        _otel: otel_guard,
+    };
+    let reload_handle = ReloadHandle {
+        reload_stdout,
+        reload_file,
+    };
+
+    Ok((guards, reload_handle))
 }
 
 /// Log security event
@@ -267,6 +1001,20 @@ pub fn log_security_event(
     user_id: Option<&str>,
     details: HashMap<String, Value>
 ) {
+    #[cfg(all(tracing_unstable, feature = "valuable"))]
+    {
+        let details: &dyn valuable::Valuable = &valuable_json::JsonMap(&details);
+        tracing::warn!(
+            target: "security",
+            event_type = ?event_type,
+            user_id = user_id,
+            details = details,
+            security_event = true,
+            "Security event occurred"
+        );
+    }
+
+    #[cfg(not(all(tracing_unstable, feature = "valuable")))]
     tracing::warn!(
         target: "security",
         event_type = ?event_type,
@@ -279,6 +1027,31 @@ pub fn log_security_event(
 
 /// Log performance metric
 pub fn log_performance_metric(metric: PerformanceMetric) {
+    if let Some(histogram) = PERFORMANCE_DURATION_HISTOGRAM.get() {
+        histogram.record(
+            metric.duration_ms,
+            &[
+                KeyValue::new("operation", metric.operation.clone()),
+                KeyValue::new("success", metric.success),
+            ],
+        );
+    }
+
+    #[cfg(all(tracing_unstable, feature = "valuable"))]
+    {
+        let details: &dyn valuable::Valuable = &valuable_json::JsonMap(&metric.details);
+        tracing::info!(
+            target: "performance",
+            operation = %metric.operation,
+            duration_ms = metric.duration_ms,
+            success = metric.success,
+            details = details,
+            performance_metric = true,
+            "Performance metric recorded"
+        );
+    }
+
+    #[cfg(not(all(tracing_unstable, feature = "valuable")))]
     tracing::info!(
         target: "performance",
         operation = %metric.operation,
@@ -296,6 +1069,20 @@ pub fn log_business_event(
     user_id: Option<&str>,
     details: HashMap<String, Value>
 ) {
+    #[cfg(all(tracing_unstable, feature = "valuable"))]
+    {
+        let details: &dyn valuable::Valuable = &valuable_json::JsonMap(&details);
+        tracing::info!(
+            target: "business",
+            event_type = ?event_type,
+            user_id = user_id,
+            details = details,
+            business_event = true,
+            "Business event occurred"
+        );
+    }
+
+    #[cfg(not(all(tracing_unstable, feature = "valuable")))]
     tracing::info!(
         target: "business",
         event_type = ?event_type,
@@ -334,8 +1121,11 @@ where
 {
     let span = tracing::info_span!("request", correlation_id = %correlation_id.0);
     let _enter = span.enter();
-    
-    span.record("correlation_id", &tracing::field::display(&correlation_id.0));
+
+    span.record("correlation_id", tracing::field::display(&correlation_id.0));
+    // Promote the correlation ID to an OTEL span attribute so distributed traces
+    // stay linkable even when the exporting layer isn't the one that created the span.
+    span.set_attribute("correlation_id", correlation_id.0.clone());
     f()
 }
 
@@ -347,7 +1137,8 @@ where
     let correlation_id = CorrelationId::new();
     let span = tracing::info_span!(name, correlation_id = %correlation_id.0);
     let _enter = span.enter();
-    
+
+    span.set_attribute("correlation_id", correlation_id.0.clone());
     f(correlation_id)
 }
 
@@ -417,9 +1208,132 @@ mod tests {
             success: true,
             details: HashMap::new(),
         };
-        
+
         assert_eq!(metric.operation, "test_operation");
         assert_eq!(metric.duration_ms, 100.0);
         assert!(metric.success);
     }
+
+    #[test]
+    fn test_log_output_from_str() {
+        assert_eq!("json".parse::<LogOutput>().unwrap(), LogOutput::Json);
+        assert_eq!("Pretty".parse::<LogOutput>().unwrap(), LogOutput::Pretty);
+        assert_eq!("compact".parse::<LogOutput>().unwrap(), LogOutput::Compact);
+        assert_eq!("log".parse::<LogOutput>().unwrap(), LogOutput::Log);
+        assert_eq!("none".parse::<LogOutput>().unwrap(), LogOutput::None);
+    }
+
+    #[test]
+    fn test_log_output_from_str_rejects_unknown() {
+        let err = "verbose".parse::<LogOutput>().unwrap_err();
+        assert!(err.to_string().contains("verbose"));
+    }
+
+    #[test]
+    fn test_sanitize_entity_id_accepts_normal_id() {
+        assert_eq!(
+            sanitize_entity_id("grid-order-123"),
+            Some("grid-order-123".to_string())
+        );
+    }
+
+    #[test]
+    fn test_sanitize_entity_id_rejects_path_traversal() {
+        assert_eq!(sanitize_entity_id(".."), None);
+        assert_eq!(sanitize_entity_id("."), None);
+        assert_eq!(sanitize_entity_id("../../etc/passwd"), None);
+        assert_eq!(sanitize_entity_id("/etc/passwd"), None);
+        assert_eq!(sanitize_entity_id("orders/../../secrets"), None);
+    }
+
+    #[test]
+    fn test_sanitize_entity_id_rejects_empty() {
+        assert_eq!(sanitize_entity_id(""), None);
+        assert_eq!(sanitize_entity_id("   "), None);
+    }
+
+    #[cfg(all(tracing_unstable, feature = "valuable"))]
+    #[test]
+    fn test_valuable_json_round_trip() {
+        use valuable::Valuable;
+
+        let mut details = HashMap::new();
+        details.insert("count".to_string(), json!(3));
+        details.insert("tags".to_string(), json!(["a", "b"]));
+        details.insert("nested".to_string(), json!({"flag": true}));
+
+        let map = valuable_json::JsonMap(&details);
+        let result = valuable_json::to_json(map.as_value());
+
+        assert_eq!(result["count"], json!(3));
+        assert_eq!(result["tags"], json!(["a", "b"]));
+        assert_eq!(result["nested"], json!({"flag": true}));
+    }
+
+    /// Unique-per-test scratch directory under the OS temp dir, cleaned up
+    /// before use in case a previous run was interrupted.
+    fn per_entity_test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "off_the_grid_per_entity_test_{name}_{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_per_entity_file_layer_resolves_by_field_priority() {
+        let dir = per_entity_test_dir("priority");
+        let subscriber =
+            tracing_subscriber::registry().with(PerEntityFileLayer::with_base_dir(dir.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            // correlation_id is declared before grid_order_id, but grid_order_id
+            // is the higher-priority field and must still win.
+            let span =
+                tracing::info_span!("order", correlation_id = "corr-1", grid_order_id = "grid-1");
+            let _enter = span.enter();
+            tracing::info!(amount = 100, "order created");
+        });
+
+        assert!(dir.join("grid-1.log").exists());
+        assert!(!dir.join("corr-1.log").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_per_entity_file_layer_picks_up_late_field() {
+        let dir = per_entity_test_dir("late");
+        let subscriber =
+            tracing_subscriber::registry().with(PerEntityFileLayer::with_base_dir(dir.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("order", grid_order_id = tracing::field::Empty);
+            let _enter = span.enter();
+            span.record("grid_order_id", "late-order-999");
+            tracing::info!(amount = 55, "order created late");
+        });
+
+        assert!(dir.join("late-order-999.log").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_per_entity_file_layer_child_span_event_goes_to_ancestor_file() {
+        let dir = per_entity_test_dir("child");
+        let subscriber =
+            tracing_subscriber::registry().with(PerEntityFileLayer::with_base_dir(dir.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let parent = tracing::info_span!("order", grid_order_id = "grid-parent");
+            let _parent_enter = parent.enter();
+            let child = tracing::info_span!("step");
+            let _child_enter = child.enter();
+            tracing::info!(amount = 10, "child event");
+        });
+
+        let content = std::fs::read_to_string(dir.join("grid-parent.log")).unwrap();
+        assert!(content.contains("child event"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
\ No newline at end of file